@@ -0,0 +1,132 @@
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::persistence::config::ChecksumAlgorithm;
+use crate::persistence::errors::PersistenceError;
+use crate::persistence::PersistentId;
+
+/// A checksum computed over a single serialized snapshot chunk, tagged with
+/// the algorithm that produced it so stores mixing algorithms over time
+/// (after a config change) remain readable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checksum {
+    Disabled,
+    Md5(Vec<u8>),
+    Sha1(Vec<u8>),
+    Sha256(Vec<u8>),
+}
+
+impl Checksum {
+    /// Computes the checksum of `chunk` per `algorithm`. Stored alongside
+    /// the chunk's entry in the metadata backend at write time.
+    pub fn compute(algorithm: ChecksumAlgorithm, chunk: &[u8]) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Disabled => Self::Disabled,
+            ChecksumAlgorithm::Md5 => Self::Md5(Md5::digest(chunk).to_vec()),
+            ChecksumAlgorithm::Sha1 => Self::Sha1(Sha1::digest(chunk).to_vec()),
+            ChecksumAlgorithm::Sha256 => Self::Sha256(Sha256::digest(chunk).to_vec()),
+        }
+    }
+
+    /// Recomputes the checksum of `chunk` with the same algorithm as `self`
+    /// and compares it, returning an error naming `persistent_id` and
+    /// `offset` on divergence instead of letting corrupted bytes reach the
+    /// deserializer.
+    pub fn verify(
+        &self,
+        chunk: &[u8],
+        persistent_id: PersistentId,
+        offset: usize,
+    ) -> Result<(), PersistenceError> {
+        let algorithm = match self {
+            Self::Disabled => return Ok(()),
+            Self::Md5(_) => ChecksumAlgorithm::Md5,
+            Self::Sha1(_) => ChecksumAlgorithm::Sha1,
+            Self::Sha256(_) => ChecksumAlgorithm::Sha256,
+        };
+        if *self == Self::compute(algorithm, chunk) {
+            Ok(())
+        } else {
+            Err(PersistenceError::ChecksumMismatch {
+                persistent_id,
+                offset,
+            })
+        }
+    }
+
+    /// Serializes `self` as a leading algorithm tag followed by the raw
+    /// digest bytes, so it can be stored as an ordinary value in a
+    /// `MetadataBackend`/`KVStore`.
+    pub fn encode(&self) -> Vec<u8> {
+        let (tag, digest): (u8, &[u8]) = match self {
+            Self::Disabled => (0, &[]),
+            Self::Md5(digest) => (1, digest),
+            Self::Sha1(digest) => (2, digest),
+            Self::Sha256(digest) => (3, digest),
+        };
+        let mut encoded = Vec::with_capacity(1 + digest.len());
+        encoded.push(tag);
+        encoded.extend_from_slice(digest);
+        encoded
+    }
+
+    /// Inverse of [`Self::encode`].
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let (&tag, digest) = bytes.split_first()?;
+        match tag {
+            0 => Some(Self::Disabled),
+            1 => Some(Self::Md5(digest.to_vec())),
+            2 => Some(Self::Sha1(digest.to_vec())),
+            3 => Some(Self::Sha256(digest.to_vec())),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_accepts_matching_chunk() {
+        let checksum = Checksum::compute(ChecksumAlgorithm::Sha256, b"hello world");
+        assert!(checksum.verify(b"hello world", 42, 0).is_ok());
+    }
+
+    #[test]
+    fn verify_rejects_corrupted_chunk() {
+        let checksum = Checksum::compute(ChecksumAlgorithm::Sha256, b"hello world");
+        let err = checksum.verify(b"hello WORLD", 42, 7).unwrap_err();
+        match err {
+            PersistenceError::ChecksumMismatch {
+                persistent_id,
+                offset,
+            } => {
+                assert_eq!(persistent_id, 42);
+                assert_eq!(offset, 7);
+            }
+            other => panic!("expected ChecksumMismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn disabled_checksum_never_fails_verification() {
+        let checksum = Checksum::compute(ChecksumAlgorithm::Disabled, b"hello world");
+        assert!(checksum.verify(b"anything at all", 1, 0).is_ok());
+    }
+
+    #[test]
+    fn encode_decode_round_trips_for_every_algorithm() {
+        for algorithm in [
+            ChecksumAlgorithm::Disabled,
+            ChecksumAlgorithm::Md5,
+            ChecksumAlgorithm::Sha1,
+            ChecksumAlgorithm::Sha256,
+        ] {
+            let checksum = Checksum::compute(algorithm, b"payload");
+            let decoded = Checksum::decode(&checksum.encode()).unwrap();
+            assert_eq!(checksum, decoded);
+        }
+    }
+}
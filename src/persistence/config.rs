@@ -0,0 +1,59 @@
+//! Configuration knobs for the persistence subsystem.
+//!
+//! These are threaded through the snapshot writers and metadata backends so
+//! that the on-disk layout a given worker produces is fully determined by
+//! its config, rather than by compile-time constants.
+
+/// Controls how snapshot payloads are laid out in the backing store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapshotStorageMode {
+    /// Write each snapshot as its own blob, one put per persisted frontier.
+    /// This is the historical behavior.
+    #[default]
+    Append,
+
+    /// Store each serialized record (or fixed-size chunk) exactly once,
+    /// keyed by its content hash, and record a snapshot as a manifest: the
+    /// ordered list of the hashes it references. The manifest holds no
+    /// diff/time/frontier metadata of its own. This deduplicates payloads
+    /// that are unchanged across snapshots and across persistent objects
+    /// sharing the same `PersistentId`.
+    ContentAddressed,
+}
+
+/// Selects the hash function used to turn an `ExternalPersistentId` into the
+/// `PersistentId` under which a store's directories/keys are named.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PersistentIdHashAlgorithm {
+    /// `Xxh3::digest128`. Fast, and the historical default, but not
+    /// reproducible outside of a Rust process linking `xxhash-rust`.
+    #[default]
+    Xxh3,
+
+    /// SHA-256, truncated to the leading 16 bytes to produce the 128-bit
+    /// `PersistentId`. Slower, but reproducible by any language or shell
+    /// script that can compute a SHA-256 digest.
+    Sha256,
+}
+
+/// Selects the checksum used to verify that a recovered snapshot chunk
+/// matches what was written, the way a compiler embeds source-file hashes
+/// so a reader can prove the bytes it loaded are the bytes that were
+/// compiled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumAlgorithm {
+    /// Skip computing and verifying checksums entirely.
+    #[default]
+    Disabled,
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+/// Top-level persistence configuration.
+#[derive(Debug, Clone, Default)]
+pub struct PersistenceConfig {
+    pub snapshot_storage_mode: SnapshotStorageMode,
+    pub persistent_id_hash_algorithm: PersistentIdHashAlgorithm,
+    pub checksum_algorithm: ChecksumAlgorithm,
+}
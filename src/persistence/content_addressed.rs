@@ -0,0 +1,341 @@
+use std::io;
+
+use xxhash_rust::xxh3::Xxh3 as Hasher;
+
+use crate::persistence::checksum::Checksum;
+use crate::persistence::config::ChecksumAlgorithm;
+use crate::persistence::errors::PersistenceError;
+use crate::persistence::metadata_backends::MetadataBackend;
+use crate::persistence::PersistentId;
+
+/// Content hash identifying a single stored chunk, computed with `Xxh3`.
+/// This is an internal storage-layout detail of content-addressed mode, not
+/// an externally reproducible identifier -- unlike `PersistentId` (see
+/// [`IntoPersistentId`](crate::persistence::IntoPersistentId)), nothing
+/// outside this store needs to recompute it, so it does not follow
+/// `config::PersistentIdHashAlgorithm`.
+pub type ContentHash = u128;
+
+fn content_hash(bytes: &[u8]) -> ContentHash {
+    let mut hasher = Hasher::default();
+    hasher.update(bytes);
+    hasher.digest128()
+}
+
+fn content_key(hash: ContentHash) -> String {
+    format!("content/{hash:032x}")
+}
+
+fn checksum_key(hash: ContentHash) -> String {
+    format!("content/{hash:032x}.checksum")
+}
+
+fn encode_manifest(hashes: &[ContentHash]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(hashes.len() * 16);
+    for hash in hashes {
+        encoded.extend_from_slice(&hash.to_le_bytes());
+    }
+    encoded
+}
+
+/// Writes snapshot payloads in
+/// [`SnapshotStorageMode::ContentAddressed`](crate::persistence::config::SnapshotStorageMode::ContentAddressed)
+/// mode: each chunk is stored exactly once, keyed by its content hash, and a
+/// snapshot becomes the ordered list of hashes (its manifest) that make it
+/// up. Unchanged chunks that reappear in a later snapshot, or in a
+/// different `PersistentId` sharing the same backend, are never written
+/// twice.
+///
+/// Unlike the initial version of this writer, it does not hold a reference
+/// to its backend: callers (e.g.
+/// [`KVStoreSnapshotWriter`](crate::persistence::kv_snapshot_writer::KVStoreSnapshotWriter))
+/// pass the backend in on each call, so the writer itself can live as a
+/// plain field without borrow-checker contortions.
+#[derive(Debug, Default)]
+pub struct ContentAddressedWriter {
+    manifest: Vec<ContentHash>,
+}
+
+impl ContentAddressedWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `chunk` via `backend`, skipping the put if an identical chunk
+    /// already exists, and appends its hash to the in-progress manifest. If
+    /// `checksum_algorithm` isn't [`ChecksumAlgorithm::Disabled`], a checksum
+    /// of `chunk` is stored alongside it (once per unique chunk, like the
+    /// chunk itself) for [`Self::read_manifest_verified`] to check on
+    /// recovery.
+    pub fn write_chunk<B: MetadataBackend>(
+        &mut self,
+        backend: &mut B,
+        chunk: &[u8],
+        checksum_algorithm: ChecksumAlgorithm,
+    ) -> io::Result<()> {
+        let hash = content_hash(chunk);
+        let key = content_key(hash);
+        if backend.get_value(&key)?.is_none() {
+            backend.put_value(&key, chunk.to_vec())?;
+            if checksum_algorithm != ChecksumAlgorithm::Disabled {
+                let checksum = Checksum::compute(checksum_algorithm, chunk);
+                backend.put_value(&checksum_key(hash), checksum.encode())?;
+            }
+        }
+        self.manifest.push(hash);
+        Ok(())
+    }
+
+    /// Finalizes the snapshot by writing its manifest under `manifest_key`
+    /// and clearing the in-progress manifest so the writer can be reused for
+    /// the next snapshot.
+    pub fn finish<B: MetadataBackend>(&mut self, backend: &mut B, manifest_key: &str) -> io::Result<()> {
+        let encoded = encode_manifest(&self.manifest);
+        self.manifest.clear();
+        backend.put_value(manifest_key, encoded)
+    }
+
+    /// True if any chunk has been written since the last `finish`.
+    pub fn has_pending_chunks(&self) -> bool {
+        !self.manifest.is_empty()
+    }
+
+    /// Resolves a previously written manifest back into its ordered chunks.
+    /// Returns `Ok(None)` only when `manifest_key` itself is absent; a
+    /// manifest that exists but references a chunk hash no longer present
+    /// in the backend is corruption, not "no snapshot," and is reported as
+    /// [`PersistenceError::MissingManifestChunk`].
+    pub fn read_manifest<B: MetadataBackend>(
+        backend: &B,
+        manifest_key: &str,
+    ) -> Result<Option<Vec<Vec<u8>>>, PersistenceError> {
+        let Some(encoded) = backend.get_value(manifest_key)? else {
+            return Ok(None);
+        };
+        let mut chunks = Vec::with_capacity(encoded.len() / 16);
+        for (offset, raw) in encoded.chunks_exact(16).enumerate() {
+            let hash = ContentHash::from_le_bytes(raw.try_into().unwrap());
+            let chunk = backend.get_value(&content_key(hash))?.ok_or_else(|| {
+                PersistenceError::MissingManifestChunk {
+                    manifest_key: manifest_key.to_string(),
+                    offset,
+                }
+            })?;
+            chunks.push(chunk);
+        }
+        Ok(Some(chunks))
+    }
+
+    /// Like [`Self::read_manifest`], but recomputes and checks each chunk's
+    /// checksum (when one was stored) before returning it, aborting with a
+    /// [`PersistenceError::ChecksumMismatch`] naming `persistent_id` and the
+    /// chunk's offset within the manifest on divergence, instead of handing
+    /// back corrupted bytes for the caller to deserialize. A dangling chunk
+    /// reference is reported the same way as in [`Self::read_manifest`].
+    pub fn read_manifest_verified<B: MetadataBackend>(
+        backend: &B,
+        manifest_key: &str,
+        persistent_id: PersistentId,
+    ) -> Result<Option<Vec<Vec<u8>>>, PersistenceError> {
+        let Some(encoded) = backend.get_value(manifest_key)? else {
+            return Ok(None);
+        };
+        let mut chunks = Vec::with_capacity(encoded.len() / 16);
+        for (offset, raw) in encoded.chunks_exact(16).enumerate() {
+            let hash = ContentHash::from_le_bytes(raw.try_into().unwrap());
+            let chunk = backend.get_value(&content_key(hash))?.ok_or_else(|| {
+                PersistenceError::MissingManifestChunk {
+                    manifest_key: manifest_key.to_string(),
+                    offset,
+                }
+            })?;
+            if let Some(checksum_bytes) = backend.get_value(&checksum_key(hash))? {
+                if let Some(checksum) = Checksum::decode(&checksum_bytes) {
+                    checksum.verify(&chunk, persistent_id, offset)?;
+                }
+            }
+            chunks.push(chunk);
+        }
+        Ok(Some(chunks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::kv_store::{InMemoryKVStore, KVStore};
+
+    #[test]
+    fn dedup_skips_second_identical_write_chunk() {
+        let mut backend = InMemoryKVStore::default();
+        let mut writer = ContentAddressedWriter::new();
+
+        writer
+            .write_chunk(&mut backend, b"same-payload", ChecksumAlgorithm::Disabled)
+            .unwrap();
+        writer
+            .write_chunk(&mut backend, b"same-payload", ChecksumAlgorithm::Disabled)
+            .unwrap();
+
+        let key = content_key(content_hash(b"same-payload"));
+        assert_eq!(backend.list_keys("content/").unwrap().len(), 1);
+        assert_eq!(
+            backend.get_value(&key).unwrap(),
+            Some(b"same-payload".to_vec())
+        );
+    }
+
+    #[test]
+    fn read_manifest_round_trips_chunk_order() {
+        let mut backend = InMemoryKVStore::default();
+        let mut writer = ContentAddressedWriter::new();
+
+        writer
+            .write_chunk(&mut backend, b"first", ChecksumAlgorithm::Disabled)
+            .unwrap();
+        writer
+            .write_chunk(&mut backend, b"second", ChecksumAlgorithm::Disabled)
+            .unwrap();
+        writer
+            .write_chunk(&mut backend, b"first", ChecksumAlgorithm::Disabled)
+            .unwrap();
+        writer.finish(&mut backend, "manifest/0").unwrap();
+
+        let chunks = ContentAddressedWriter::read_manifest(&backend, "manifest/0")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            chunks,
+            vec![b"first".to_vec(), b"second".to_vec(), b"first".to_vec()]
+        );
+    }
+
+    #[test]
+    fn finish_resets_manifest_for_reuse() {
+        let mut backend = InMemoryKVStore::default();
+        let mut writer = ContentAddressedWriter::new();
+
+        writer
+            .write_chunk(&mut backend, b"a", ChecksumAlgorithm::Disabled)
+            .unwrap();
+        writer.finish(&mut backend, "manifest/0").unwrap();
+        assert!(!writer.has_pending_chunks());
+
+        writer
+            .write_chunk(&mut backend, b"b", ChecksumAlgorithm::Disabled)
+            .unwrap();
+        writer.finish(&mut backend, "manifest/1").unwrap();
+
+        assert_eq!(
+            ContentAddressedWriter::read_manifest(&backend, "manifest/0")
+                .unwrap()
+                .unwrap(),
+            vec![b"a".to_vec()]
+        );
+        assert_eq!(
+            ContentAddressedWriter::read_manifest(&backend, "manifest/1")
+                .unwrap()
+                .unwrap(),
+            vec![b"b".to_vec()]
+        );
+    }
+
+    #[test]
+    fn read_manifest_verified_accepts_untampered_chunks() {
+        let mut backend = InMemoryKVStore::default();
+        let mut writer = ContentAddressedWriter::new();
+
+        writer
+            .write_chunk(&mut backend, b"a", ChecksumAlgorithm::Sha256)
+            .unwrap();
+        writer.finish(&mut backend, "manifest/0").unwrap();
+
+        let chunks =
+            ContentAddressedWriter::read_manifest_verified(&backend, "manifest/0", 7)
+                .unwrap()
+                .unwrap();
+        assert_eq!(chunks, vec![b"a".to_vec()]);
+    }
+
+    #[test]
+    fn read_manifest_verified_rejects_corrupted_chunk() {
+        let mut backend = InMemoryKVStore::default();
+        let mut writer = ContentAddressedWriter::new();
+
+        writer
+            .write_chunk(&mut backend, b"a", ChecksumAlgorithm::Sha256)
+            .unwrap();
+        writer.finish(&mut backend, "manifest/0").unwrap();
+
+        // Corrupt the stored chunk without touching its checksum entry.
+        let key = content_key(content_hash(b"a"));
+        backend.put_value(&key, b"tampered".to_vec()).unwrap();
+
+        let err = ContentAddressedWriter::read_manifest_verified(&backend, "manifest/0", 7)
+            .unwrap_err();
+        match err {
+            PersistenceError::ChecksumMismatch {
+                persistent_id,
+                offset,
+            } => {
+                assert_eq!(persistent_id, 7);
+                assert_eq!(offset, 0);
+            }
+            other => panic!("expected ChecksumMismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn read_manifest_rejects_dangling_chunk_reference() {
+        let mut backend = InMemoryKVStore::default();
+        let mut writer = ContentAddressedWriter::new();
+
+        writer
+            .write_chunk(&mut backend, b"a", ChecksumAlgorithm::Disabled)
+            .unwrap();
+        writer.finish(&mut backend, "manifest/0").unwrap();
+
+        // Remove the chunk itself while leaving the manifest pointing at it.
+        let key = content_key(content_hash(b"a"));
+        backend.remove(&key).unwrap();
+
+        let err = ContentAddressedWriter::read_manifest(&backend, "manifest/0").unwrap_err();
+        match err {
+            PersistenceError::MissingManifestChunk {
+                manifest_key,
+                offset,
+            } => {
+                assert_eq!(manifest_key, "manifest/0");
+                assert_eq!(offset, 0);
+            }
+            other => panic!("expected MissingManifestChunk, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn read_manifest_verified_rejects_dangling_chunk_reference() {
+        let mut backend = InMemoryKVStore::default();
+        let mut writer = ContentAddressedWriter::new();
+
+        writer
+            .write_chunk(&mut backend, b"a", ChecksumAlgorithm::Sha256)
+            .unwrap();
+        writer.finish(&mut backend, "manifest/0").unwrap();
+
+        let key = content_key(content_hash(b"a"));
+        backend.remove(&key).unwrap();
+
+        let err = ContentAddressedWriter::read_manifest_verified(&backend, "manifest/0", 7)
+            .unwrap_err();
+        match err {
+            PersistenceError::MissingManifestChunk {
+                manifest_key,
+                offset,
+            } => {
+                assert_eq!(manifest_key, "manifest/0");
+                assert_eq!(offset, 0);
+            }
+            other => panic!("expected MissingManifestChunk, got {other:?}"),
+        }
+    }
+}
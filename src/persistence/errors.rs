@@ -0,0 +1,81 @@
+use std::fmt;
+use std::io;
+
+use crate::persistence::config::PersistentIdHashAlgorithm;
+use crate::persistence::PersistentId;
+
+/// Errors raised while reading or writing persisted state.
+#[derive(Debug)]
+pub enum PersistenceError {
+    /// The hash algorithm configured for this worker doesn't match the one
+    /// recorded in the store's metadata header, so `PersistentId`s computed
+    /// locally would not match the ones already on disk.
+    HashAlgorithmMismatch {
+        configured: PersistentIdHashAlgorithm,
+        stored: PersistentIdHashAlgorithm,
+    },
+
+    /// A recovered snapshot chunk's checksum doesn't match the one recorded
+    /// alongside it, so the bytes were corrupted (or truncated) since they
+    /// were written.
+    ChecksumMismatch {
+        persistent_id: PersistentId,
+        offset: usize,
+    },
+
+    /// The backing `KVStore`/`MetadataBackend` failed while reading or
+    /// writing.
+    Io(io::Error),
+
+    /// A store's `StoreMetadataHeader` entry exists but couldn't be decoded
+    /// (wrong length, unrecognized algorithm tag, ...). Treated as
+    /// corruption rather than "no header yet," since defaulting to the
+    /// configured algorithm here would silently generate colliding or
+    /// orphaned `PersistentId`s exactly like an unchecked header would.
+    CorruptHeader,
+
+    /// A content-addressed manifest is present but references a chunk hash
+    /// that no longer exists in the backend. The manifest itself was found,
+    /// so this is data loss within the store -- it must not be conflated
+    /// with "no snapshot at all" (`Ok(None)`).
+    MissingManifestChunk { manifest_key: String, offset: usize },
+}
+
+impl fmt::Display for PersistenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HashAlgorithmMismatch { configured, stored } => write!(
+                f,
+                "store was written with persistent-id hash algorithm {stored:?}, \
+                 but this worker is configured to use {configured:?}"
+            ),
+            Self::ChecksumMismatch {
+                persistent_id,
+                offset,
+            } => write!(
+                f,
+                "checksum mismatch recovering persistent id {persistent_id} at snapshot offset {offset}"
+            ),
+            Self::Io(e) => write!(f, "persistence backend IO error: {e}"),
+            Self::CorruptHeader => {
+                write!(f, "store metadata header is present but could not be decoded")
+            }
+            Self::MissingManifestChunk {
+                manifest_key,
+                offset,
+            } => write!(
+                f,
+                "manifest {manifest_key} references a chunk at offset {offset} \
+                 that is missing from the backend"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PersistenceError {}
+
+impl From<io::Error> for PersistenceError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,190 @@
+use crate::connectors::snapshot::SnapshotWriter;
+use crate::persistence::checksum::Checksum;
+use crate::persistence::config::{ChecksumAlgorithm, SnapshotStorageMode};
+use crate::persistence::content_addressed::ContentAddressedWriter;
+use crate::persistence::errors::PersistenceError;
+use crate::persistence::kv_store::KVStore;
+use crate::persistence::{ExternalPersistentId, PersistentId};
+
+fn checksum_key(key: &str) -> String {
+    format!("{key}.checksum")
+}
+
+/// A [`SnapshotWriter`] implementation backed by any [`KVStore`], so
+/// snapshots and frontier metadata both go through the same storage
+/// abstraction used by [`metadata_backends`](crate::persistence::metadata_backends)
+/// instead of a dedicated filesystem/S3 code path. Append-mode entries and
+/// content-addressed manifests are keyed as
+/// `{external_persistent_id}/{sequence number}` (checksums as
+/// `{key}.checksum`); content-addressed chunks live under `content/`.
+///
+/// `mode` selects between [`SnapshotStorageMode::Append`] (each `write` is
+/// its own entry) and [`SnapshotStorageMode::ContentAddressed`] (each
+/// `write` is a deduplicated chunk folded into a manifest that's only
+/// committed on `flush`) -- this is the dispatch point for the config flag
+/// described in `config::SnapshotStorageMode`. `checksum_algorithm` selects
+/// whether a checksum is stored alongside each chunk for
+/// [`KVStoreSnapshotReader`] to verify on recovery.
+pub struct KVStoreSnapshotWriter<S: KVStore> {
+    store: S,
+    external_persistent_id: ExternalPersistentId,
+    next_sequence_number: u64,
+    mode: SnapshotStorageMode,
+    checksum_algorithm: ChecksumAlgorithm,
+    content_addressed: ContentAddressedWriter,
+}
+
+impl<S: KVStore> KVStoreSnapshotWriter<S> {
+    pub fn new(
+        store: S,
+        external_persistent_id: ExternalPersistentId,
+        mode: SnapshotStorageMode,
+        checksum_algorithm: ChecksumAlgorithm,
+    ) -> Self {
+        Self {
+            store,
+            external_persistent_id,
+            next_sequence_number: 0,
+            mode,
+            checksum_algorithm,
+            content_addressed: ContentAddressedWriter::new(),
+        }
+    }
+
+    fn next_key(&mut self) -> String {
+        let key = format!(
+            "{}/{}",
+            self.external_persistent_id, self.next_sequence_number
+        );
+        self.next_sequence_number += 1;
+        key
+    }
+}
+
+impl<S: KVStore> SnapshotWriter for KVStoreSnapshotWriter<S> {
+    fn write(&mut self, data: &[u8]) {
+        // `SnapshotWriter` has no error channel of its own, so a `KVStore`
+        // failure here surfaces as a panic rather than being swallowed --
+        // see the chunk0-2 fix making `KVStore` itself fallible.
+        match self.mode {
+            SnapshotStorageMode::Append => {
+                let key = self.next_key();
+                self.store
+                    .put(&key, data.to_vec())
+                    .expect("KVStore put failed while writing snapshot chunk");
+                if self.checksum_algorithm != ChecksumAlgorithm::Disabled {
+                    let checksum = Checksum::compute(self.checksum_algorithm, data);
+                    self.store
+                        .put(&checksum_key(&key), checksum.encode())
+                        .expect("KVStore put failed while writing snapshot checksum");
+                }
+            }
+            SnapshotStorageMode::ContentAddressed => {
+                self.content_addressed
+                    .write_chunk(&mut self.store, data, self.checksum_algorithm)
+                    .expect("KVStore put failed while writing content-addressed chunk");
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.mode == SnapshotStorageMode::ContentAddressed && self.content_addressed.has_pending_chunks() {
+            let manifest_key = self.next_key();
+            self.content_addressed
+                .finish(&mut self.store, &manifest_key)
+                .expect("KVStore put failed while writing snapshot manifest");
+        }
+    }
+}
+
+/// Reads back snapshots written by [`KVStoreSnapshotWriter`] in
+/// [`SnapshotStorageMode::Append`], recomputing and checking each chunk's
+/// checksum (when one was stored) before handing it back, the way the
+/// `tracker`/`state` recovery path is meant to consume it. Aborts with a
+/// [`PersistenceError::ChecksumMismatch`] naming the `PersistentId` and
+/// snapshot offset on divergence rather than returning corrupted bytes.
+pub struct KVStoreSnapshotReader<'a, S: KVStore> {
+    store: &'a S,
+    external_persistent_id: &'a str,
+    persistent_id: PersistentId,
+}
+
+impl<'a, S: KVStore> KVStoreSnapshotReader<'a, S> {
+    pub fn new(
+        store: &'a S,
+        external_persistent_id: &'a str,
+        persistent_id: PersistentId,
+    ) -> Self {
+        Self {
+            store,
+            external_persistent_id,
+            persistent_id,
+        }
+    }
+
+    /// Reads and verifies the chunk at `offset` (the sequence number it was
+    /// written with), returning `Ok(None)` once `offset` is past the last
+    /// written chunk.
+    pub fn read(&self, offset: usize) -> Result<Option<Vec<u8>>, PersistenceError> {
+        let key = format!("{}/{offset}", self.external_persistent_id);
+        let Some(chunk) = self.store.get(&key)? else {
+            return Ok(None);
+        };
+        if let Some(checksum_bytes) = self.store.get(&checksum_key(&key))? {
+            if let Some(checksum) = Checksum::decode(&checksum_bytes) {
+                checksum.verify(&chunk, self.persistent_id, offset)?;
+            }
+        }
+        Ok(Some(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::kv_store::InMemoryKVStore;
+
+    #[test]
+    fn append_mode_round_trips_and_verifies() {
+        let store = InMemoryKVStore::default();
+        let mut writer = KVStoreSnapshotWriter::new(
+            store,
+            "worker-1".to_string(),
+            SnapshotStorageMode::Append,
+            ChecksumAlgorithm::Sha256,
+        );
+        writer.write(b"chunk-0");
+        writer.write(b"chunk-1");
+
+        let reader = KVStoreSnapshotReader::new(&writer.store, "worker-1", 7);
+        assert_eq!(reader.read(0).unwrap(), Some(b"chunk-0".to_vec()));
+        assert_eq!(reader.read(1).unwrap(), Some(b"chunk-1".to_vec()));
+        assert_eq!(reader.read(2).unwrap(), None);
+    }
+
+    #[test]
+    fn append_mode_detects_corruption_on_read() {
+        let store = InMemoryKVStore::default();
+        let mut writer = KVStoreSnapshotWriter::new(
+            store,
+            "worker-1".to_string(),
+            SnapshotStorageMode::Append,
+            ChecksumAlgorithm::Sha256,
+        );
+        writer.write(b"chunk-0");
+        writer.store.put("worker-1/0", b"tampered".to_vec()).unwrap();
+
+        let reader = KVStoreSnapshotReader::new(&writer.store, "worker-1", 7);
+        let err = reader.read(0).unwrap_err();
+        match err {
+            PersistenceError::ChecksumMismatch {
+                persistent_id,
+                offset,
+            } => {
+                assert_eq!(persistent_id, 7);
+                assert_eq!(offset, 0);
+            }
+            other => panic!("expected ChecksumMismatch, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Minimal key-value contract that any persistence destination (local
+/// filesystem, S3, Redis, Postgres, an embedded LMDB-style store, ...) must
+/// satisfy to back both snapshot writing and metadata persistence.
+///
+/// Callers key snapshot entries and manifests as `{ExternalPersistentId}/{snapshot
+/// sequence number}` and content-addressed chunks as `content/{hash}`, so a
+/// single store can hold snapshot manifests, their content-addressed chunks,
+/// and frontier metadata.
+///
+/// `list`'s contract is a plain string prefix match over the full key, not
+/// directory-children enumeration: `list("a/b")` returns every key starting
+/// with `"a/b"`, including e.g. `"a/bc"`, regardless of how an implementation
+/// happens to lay keys out on disk. Every implementation must honor this so
+/// swapping backends doesn't silently change which keys a given prefix
+/// returns.
+pub trait KVStore: Send + Sync {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    fn put(&mut self, key: &str, value: Vec<u8>) -> io::Result<()>;
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>>;
+    fn remove(&mut self, key: &str) -> io::Result<()>;
+}
+
+/// In-memory `KVStore`, used in tests and as a reference implementation.
+#[derive(Debug, Default)]
+pub struct InMemoryKVStore {
+    entries: HashMap<String, Vec<u8>>,
+}
+
+impl KVStore for InMemoryKVStore {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn put(&mut self, key: &str, value: Vec<u8>) -> io::Result<()> {
+        self.entries.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        Ok(self
+            .entries
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn remove(&mut self, key: &str) -> io::Result<()> {
+        self.entries.remove(key);
+        Ok(())
+    }
+}
+
+/// `KVStore` backed by a directory on the local filesystem: a key maps to a
+/// file at `root/key`, with any `/` in the key producing subdirectories.
+/// This is the existing filesystem backend, ported onto the `KVStore`
+/// abstraction.
+#[derive(Debug)]
+pub struct FilesystemKVStore {
+    root: PathBuf,
+}
+
+impl FilesystemKVStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    /// Recursively walks `dir`, appending every file's key (its path
+    /// relative to `self.root`, `/`-separated) to `out`.
+    fn collect_keys(dir: &Path, relative: &str, out: &mut Vec<String>) -> io::Result<()> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let key = if relative.is_empty() {
+                name
+            } else {
+                format!("{relative}/{name}")
+            };
+            if entry.path().is_dir() {
+                Self::collect_keys(&entry.path(), &key, out)?;
+            } else {
+                out.push(key);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl KVStore for FilesystemKVStore {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path_for(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn put(&mut self, key: &str, value: Vec<u8>) -> io::Result<()> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, value)
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        // Prefix matching is over the full key string (see the trait docs),
+        // so every key under the store has to be walked rather than just
+        // the directory named by `prefix` -- a prefix need not fall on a
+        // path boundary. A failed walk must not be mistaken for an empty
+        // store (a GC sweep over `list()` could then delete still-live
+        // chunks), so propagate the error instead of swallowing it.
+        Self::collect_keys(&self.root, "", &mut keys)?;
+        keys.retain(|key| key.starts_with(prefix));
+        Ok(keys)
+    }
+
+    fn remove(&mut self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path_for(key)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_list_matches_prefix_across_keys() {
+        let mut store = InMemoryKVStore::default();
+        store.put("a/0", b"x".to_vec()).unwrap();
+        store.put("a/1", b"y".to_vec()).unwrap();
+        store.put("b/0", b"z".to_vec()).unwrap();
+
+        let mut listed = store.list("a/").unwrap();
+        listed.sort();
+        assert_eq!(listed, vec!["a/0".to_string(), "a/1".to_string()]);
+    }
+
+    #[test]
+    fn filesystem_list_matches_in_memory_for_the_same_keys() {
+        let dir = std::env::temp_dir().join(format!(
+            "pathway-kv-store-test-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let mut fs_store = FilesystemKVStore::new(&dir);
+        let mut mem_store = InMemoryKVStore::default();
+
+        for (key, value) in [("a/0", "x"), ("a/1", "y"), ("b/0", "z")] {
+            fs_store.put(key, value.as_bytes().to_vec()).unwrap();
+            mem_store.put(key, value.as_bytes().to_vec()).unwrap();
+        }
+
+        let mut fs_listed = fs_store.list("a/").unwrap();
+        let mut mem_listed = mem_store.list("a/").unwrap();
+        fs_listed.sort();
+        mem_listed.sort();
+        assert_eq!(fs_listed, mem_listed);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn filesystem_get_put_remove_round_trip() {
+        let dir = std::env::temp_dir().join(format!(
+            "pathway-kv-store-test-round-trip-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let mut store = FilesystemKVStore::new(&dir);
+
+        assert_eq!(store.get("missing").unwrap(), None);
+        store.put("key", b"value".to_vec()).unwrap();
+        assert_eq!(store.get("key").unwrap(), Some(b"value".to_vec()));
+        store.remove("key").unwrap();
+        assert_eq!(store.get("key").unwrap(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn filesystem_list_propagates_io_errors_instead_of_returning_empty() {
+        let root = std::env::temp_dir().join(format!(
+            "pathway-kv-store-test-list-error-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&root);
+        let _ = fs::remove_dir_all(&root);
+        // A plain file where the store expects a directory makes `read_dir`
+        // fail with something other than `NotFound`, simulating a walk that
+        // hits a real IO error partway through.
+        fs::write(&root, b"not a directory").unwrap();
+        let store = FilesystemKVStore::new(&root);
+
+        assert!(store.list("a/").is_err());
+
+        fs::remove_file(&root).unwrap();
+    }
+}
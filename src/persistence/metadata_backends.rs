@@ -0,0 +1,35 @@
+use std::io;
+
+use crate::persistence::kv_store::KVStore;
+
+/// A destination capable of storing the small pieces of metadata the
+/// persistence subsystem needs: snapshot manifests, frontiers, and, in
+/// [`SnapshotStorageMode::ContentAddressed`](crate::persistence::config::SnapshotStorageMode),
+/// the deduplicated payloads those manifests reference.
+///
+/// This is now a thin, naming-compatible facade over [`KVStore`]: any
+/// `KVStore` is automatically a `MetadataBackend`, so destinations only need
+/// to implement the former. The trait itself is kept so existing callers
+/// and the `get_value`/`put_value`/`list_keys` vocabulary don't need to
+/// change.
+pub trait MetadataBackend: Send + Sync {
+    fn get_value(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    fn put_value(&mut self, key: &str, value: Vec<u8>) -> io::Result<()>;
+    fn list_keys(&self, prefix: &str) -> io::Result<Vec<String>>;
+}
+
+impl<S: KVStore> MetadataBackend for S {
+    fn get_value(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        self.get(key)
+    }
+
+    fn put_value(&mut self, key: &str, value: Vec<u8>) -> io::Result<()> {
+        self.put(key, value)
+    }
+
+    fn list_keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        self.list(prefix)
+    }
+}
+
+pub use crate::persistence::kv_store::InMemoryKVStore as InMemoryMetadataBackend;
@@ -1,11 +1,20 @@
 use std::sync::{Arc, Mutex};
 
+use sha2::{Digest, Sha256};
 use xxhash_rust::xxh3::Xxh3 as Hasher;
 
 use crate::connectors::snapshot::SnapshotWriter;
+use crate::persistence::config::PersistentIdHashAlgorithm;
+use crate::persistence::errors::PersistenceError;
+use crate::persistence::metadata_backends::MetadataBackend;
 
+pub mod checksum;
 pub mod config;
+pub mod content_addressed;
+pub mod errors;
 pub mod frontier;
+pub mod kv_snapshot_writer;
+pub mod kv_store;
 pub mod metadata_backends;
 pub mod state;
 pub mod sync;
@@ -16,13 +25,172 @@ pub type ExternalPersistentId = String;
 pub type SharedSnapshotWriter = Arc<Mutex<dyn SnapshotWriter>>;
 
 pub trait IntoPersistentId {
+    /// Hashes `self` with the default algorithm ([`PersistentIdHashAlgorithm::Xxh3`]).
     fn into_persistent_id(self) -> PersistentId;
+
+    /// Hashes `self` with an explicitly chosen algorithm, so the resulting
+    /// `PersistentId` can be reproduced outside of this process.
+    fn into_persistent_id_with(self, algorithm: PersistentIdHashAlgorithm) -> PersistentId;
 }
 
 impl IntoPersistentId for ExternalPersistentId {
     fn into_persistent_id(self) -> PersistentId {
-        let mut hasher = Hasher::default();
-        hasher.update(self.as_bytes());
-        hasher.digest128()
+        self.into_persistent_id_with(PersistentIdHashAlgorithm::Xxh3)
+    }
+
+    fn into_persistent_id_with(self, algorithm: PersistentIdHashAlgorithm) -> PersistentId {
+        match algorithm {
+            PersistentIdHashAlgorithm::Xxh3 => {
+                let mut hasher = Hasher::default();
+                hasher.update(self.as_bytes());
+                hasher.digest128()
+            }
+            PersistentIdHashAlgorithm::Sha256 => {
+                // Truncate to the leading 16 bytes rather than folding the
+                // full digest: the goal is a `PersistentId` any language or
+                // shell script can reproduce, and `sha256sum | head -c 32`
+                // (i.e. the first 16 bytes, hex-encoded) is straightforward
+                // to replicate outside of Rust, whereas XOR-folding the
+                // whole digest is not.
+                let digest = Sha256::digest(self.as_bytes());
+                let mut truncated = [0u8; 16];
+                truncated.copy_from_slice(&digest[..16]);
+                u128::from_be_bytes(truncated)
+            }
+        }
+    }
+}
+
+/// Header persisted alongside a store's data, recording which hash
+/// algorithm produced its `PersistentId`s. Opening a store whose header
+/// doesn't match the worker's configured algorithm is rejected rather than
+/// silently generating colliding or orphaned IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreMetadataHeader {
+    pub hash_algorithm: PersistentIdHashAlgorithm,
+}
+
+impl StoreMetadataHeader {
+    /// Key the header is stored under in a store's `MetadataBackend`.
+    const KEY: &'static str = "_persistence_header";
+
+    /// Checks `self` (as read from an existing store) against the algorithm
+    /// this worker is configured to use.
+    pub fn validate(&self, configured: PersistentIdHashAlgorithm) -> Result<(), PersistenceError> {
+        if self.hash_algorithm == configured {
+            Ok(())
+        } else {
+            Err(PersistenceError::HashAlgorithmMismatch {
+                configured,
+                stored: self.hash_algorithm,
+            })
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let tag: u8 = match self.hash_algorithm {
+            PersistentIdHashAlgorithm::Xxh3 => 0,
+            PersistentIdHashAlgorithm::Sha256 => 1,
+        };
+        vec![tag]
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let hash_algorithm = match bytes {
+            [0] => PersistentIdHashAlgorithm::Xxh3,
+            [1] => PersistentIdHashAlgorithm::Sha256,
+            _ => return None,
+        };
+        Some(Self { hash_algorithm })
+    }
+
+    /// Opens a store's header: if one is already persisted, validates it
+    /// against `configured` so a mismatched config is rejected instead of
+    /// silently generating colliding or orphaned `PersistentId`s; otherwise
+    /// writes a fresh header recording `configured` as the algorithm this
+    /// store will use from now on. Call this once, before computing any
+    /// `PersistentId`s against `backend`.
+    pub fn open_or_init<B: MetadataBackend>(
+        backend: &mut B,
+        configured: PersistentIdHashAlgorithm,
+    ) -> Result<(), PersistenceError> {
+        match backend.get_value(Self::KEY)? {
+            Some(bytes) => Self::decode(&bytes)
+                .ok_or(PersistenceError::CorruptHeader)?
+                .validate(configured),
+            None => {
+                let header = Self {
+                    hash_algorithm: configured,
+                };
+                backend.put_value(Self::KEY, header.encode())?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::kv_store::InMemoryKVStore;
+
+    #[test]
+    fn sha256_persistent_id_is_truncation_of_digest() {
+        let id = "some-external-id".to_string().into_persistent_id_with(
+            PersistentIdHashAlgorithm::Sha256,
+        );
+        let digest = Sha256::digest(b"some-external-id");
+        let mut expected = [0u8; 16];
+        expected.copy_from_slice(&digest[..16]);
+        assert_eq!(id, u128::from_be_bytes(expected));
+    }
+
+    #[test]
+    fn open_or_init_writes_header_on_first_open() {
+        let mut backend = InMemoryKVStore::default();
+        StoreMetadataHeader::open_or_init(&mut backend, PersistentIdHashAlgorithm::Sha256).unwrap();
+
+        let stored = backend.get_value(StoreMetadataHeader::KEY).unwrap().unwrap();
+        assert_eq!(
+            StoreMetadataHeader::decode(&stored).unwrap().hash_algorithm,
+            PersistentIdHashAlgorithm::Sha256
+        );
+    }
+
+    #[test]
+    fn open_or_init_rejects_mismatched_algorithm_on_reopen() {
+        let mut backend = InMemoryKVStore::default();
+        StoreMetadataHeader::open_or_init(&mut backend, PersistentIdHashAlgorithm::Xxh3).unwrap();
+
+        let err = StoreMetadataHeader::open_or_init(&mut backend, PersistentIdHashAlgorithm::Sha256)
+            .unwrap_err();
+        match err {
+            PersistenceError::HashAlgorithmMismatch { configured, stored } => {
+                assert_eq!(configured, PersistentIdHashAlgorithm::Sha256);
+                assert_eq!(stored, PersistentIdHashAlgorithm::Xxh3);
+            }
+            other => panic!("expected HashAlgorithmMismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn open_or_init_accepts_matching_algorithm_on_reopen() {
+        let mut backend = InMemoryKVStore::default();
+        StoreMetadataHeader::open_or_init(&mut backend, PersistentIdHashAlgorithm::Xxh3).unwrap();
+        assert!(
+            StoreMetadataHeader::open_or_init(&mut backend, PersistentIdHashAlgorithm::Xxh3).is_ok()
+        );
+    }
+
+    #[test]
+    fn open_or_init_rejects_corrupt_header_instead_of_defaulting() {
+        let mut backend = InMemoryKVStore::default();
+        backend
+            .put_value(StoreMetadataHeader::KEY, vec![])
+            .unwrap();
+
+        let err = StoreMetadataHeader::open_or_init(&mut backend, PersistentIdHashAlgorithm::Xxh3)
+            .unwrap_err();
+        assert!(matches!(err, PersistenceError::CorruptHeader));
     }
 }